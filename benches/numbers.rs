@@ -0,0 +1,52 @@
+//! Benchmark the numeric serialization fast path (itoa/ryu) against a document
+//! with many numeric fields.
+
+#[macro_use]
+extern crate criterion;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_xml_rs;
+
+use criterion::{Bencher, Criterion};
+use serde_xml_rs::to_string;
+
+#[derive(Serialize)]
+struct Sample {
+    a: u64,
+    b: i64,
+    c: u32,
+    d: i32,
+    e: f64,
+    f: f32,
+}
+
+#[derive(Serialize)]
+struct Document {
+    rows: Vec<Sample>,
+}
+
+fn document() -> Document {
+    let rows = (0..1024)
+        .map(|i| Sample {
+            a: i as u64,
+            b: -(i as i64),
+            c: i,
+            d: -(i as i32),
+            e: i as f64 * 1.5,
+            f: i as f32 / 3.0,
+        })
+        .collect();
+    Document { rows }
+}
+
+fn bench_numbers(b: &mut Bencher) {
+    let doc = document();
+    b.iter(|| to_string(&doc).unwrap());
+}
+
+fn benchmark(c: &mut Criterion) {
+    c.bench_function("serialize_numbers", bench_numbers);
+}
+
+criterion_group!(benches, benchmark);
+criterion_main!(benches);
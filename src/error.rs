@@ -0,0 +1,95 @@
+use std::borrow::Cow;
+use std::error::Error as StdError;
+use std::fmt::{self, Display};
+use std::io;
+use std::string::FromUtf8Error;
+
+use serde::ser;
+
+/// Convenient wrapper around `std::result::Result` for this crate.
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// The error type produced while serializing XML.
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+}
+
+/// The kinds of error that serialization can produce.
+#[derive(Debug)]
+pub enum ErrorKind {
+    /// An operation the XML serializer cannot represent was requested.
+    ///
+    /// The message is a `Cow` so static descriptions (the common case) borrow a
+    /// `&'static str` and only dynamically-built messages allocate.
+    UnsupportedOperation(Cow<'static, str>),
+    /// A message produced by `serde` via `ser::Error::custom`.
+    Custom(String),
+    /// An I/O error from the underlying writer.
+    Io(io::Error),
+    /// The serialized output was not valid UTF-8.
+    Utf8(FromUtf8Error),
+}
+
+impl Error {
+    /// The kind of this error.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error { kind: kind }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        ErrorKind::Io(err).into()
+    }
+}
+
+impl From<FromUtf8Error> for Error {
+    fn from(err: FromUtf8Error) -> Self {
+        ErrorKind::Utf8(err).into()
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ErrorKind::UnsupportedOperation(ref msg) => {
+                write!(f, "unsupported operation: {}", msg)
+            }
+            ErrorKind::Custom(ref msg) => write!(f, "{}", msg),
+            ErrorKind::Io(ref err) => Display::fmt(err, f),
+            ErrorKind::Utf8(ref err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn description(&self) -> &str {
+        match self.kind {
+            ErrorKind::UnsupportedOperation(_) => "unsupported operation",
+            ErrorKind::Custom(ref msg) => msg,
+            ErrorKind::Io(ref err) => err.description(),
+            ErrorKind::Utf8(ref err) => err.description(),
+        }
+    }
+
+    fn cause(&self) -> Option<&StdError> {
+        match self.kind {
+            ErrorKind::Io(ref err) => Some(err),
+            ErrorKind::Utf8(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        ErrorKind::Custom(msg.to_string()).into()
+    }
+}
@@ -0,0 +1,135 @@
+use std::borrow::Cow;
+use std::io::Write;
+
+use error::{ErrorKind, Result};
+
+/// Validate that `name` is a legal XML element name before it is emitted as a
+/// tag, so a malformed key (containing spaces, or starting with a digit)
+/// surfaces as an error rather than producing invalid markup.
+pub fn validate_xml_name(name: &str) -> Result<()> {
+    fn is_name_start(c: char) -> bool {
+        c == '_' || c == ':' || c.is_alphabetic()
+    }
+    fn is_name_char(c: char) -> bool {
+        is_name_start(c) || c == '-' || c == '.' || c.is_ascii_digit()
+    }
+
+    let mut chars = name.chars();
+    let valid = match chars.next() {
+        Some(c) if is_name_start(c) => chars.all(is_name_char),
+        _ => false,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ErrorKind::UnsupportedOperation(
+            Cow::Owned(format!("`{}` is not a valid XML name", name)),
+        ).into())
+    }
+}
+
+/// Write `value` to `writer`, escaping the characters that are significant in
+/// XML text content (`&`, `<`, `>`).
+///
+/// Escaped bytes are streamed straight to the sink and the replacement
+/// entities are `'static` byte strings, so nothing is allocated and no
+/// intermediate `String` is built.
+pub fn escape_text<W: Write>(writer: &mut W, value: &str) -> Result<()> {
+    escape(writer, value, false)
+}
+
+/// Write `value` to `writer`, escaping text characters plus the quoting
+/// characters (`"`, `'`) that would otherwise terminate an attribute value.
+pub fn escape_attribute<W: Write>(writer: &mut W, value: &str) -> Result<()> {
+    escape(writer, value, true)
+}
+
+fn escape<W: Write>(writer: &mut W, value: &str, attribute: bool) -> Result<()> {
+    let bytes = value.as_bytes();
+    let mut last = 0;
+
+    for (i, &byte) in bytes.iter().enumerate() {
+        let replacement: &[u8] = match byte {
+            b'&' => b"&amp;",
+            b'<' => b"&lt;",
+            b'>' => b"&gt;",
+            b'"' if attribute => b"&quot;",
+            b'\'' if attribute => b"&apos;",
+            _ => continue,
+        };
+
+        writer.write_all(&bytes[last..i])?;
+        writer.write_all(replacement)?;
+        last = i + 1;
+    }
+
+    writer.write_all(&bytes[last..])?;
+    Ok(())
+}
+
+/// Write `value` to `writer` wrapped in a `<![CDATA[ ... ]]>` section instead of
+/// entity-escaping its contents.
+///
+/// A literal `]]>` cannot appear inside a CDATA section, so any occurrence is
+/// split across two sections (`]]` + `]]><![CDATA[` + `>`) as required by the
+/// XML specification.
+pub fn write_cdata<W: Write>(writer: &mut W, value: &str) -> Result<()> {
+    write!(writer, "<![CDATA[")?;
+
+    let mut rest = value;
+    while let Some(idx) = rest.find("]]>") {
+        write!(writer, "{}]]]]><![CDATA[>", &rest[..idx])?;
+        rest = &rest[idx + 3..];
+    }
+    write!(writer, "{}]]>", rest)?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escaped_text(value: &str) -> String {
+        let mut buffer = Vec::new();
+        escape_text(&mut buffer, value).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    fn escaped_attribute(value: &str) -> String {
+        let mut buffer = Vec::new();
+        escape_attribute(&mut buffer, value).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    fn cdata(value: &str) -> String {
+        let mut buffer = Vec::new();
+        write_cdata(&mut buffer, value).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn text_escapes_markup_but_not_quotes() {
+        assert_eq!(escaped_text("<a & \"b\">"), "&lt;a &amp; \"b\"&gt;");
+    }
+
+    #[test]
+    fn attribute_escapes_quotes_as_well() {
+        assert_eq!(
+            escaped_attribute("<a & \"b\" 'c'>"),
+            "&lt;a &amp; &quot;b&quot; &apos;c&apos;&gt;"
+        );
+    }
+
+    #[test]
+    fn all_safe_content_is_written_verbatim() {
+        assert_eq!(escaped_text("plain text 42"), "plain text 42");
+    }
+
+    #[test]
+    fn cdata_splits_literal_terminator() {
+        // `]]>` cannot appear inside a section, so it is split across two.
+        assert_eq!(cdata("a]]>b"), "<![CDATA[a]]]]><![CDATA[>b]]>");
+    }
+}
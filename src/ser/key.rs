@@ -0,0 +1,263 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+
+use serde::ser::{self, Impossible, Serialize};
+
+use error::{Error, ErrorKind, Result};
+use super::helpers;
+
+/// A `Serializer` that renders a value to the text used for a tag name or an
+/// attribute value.
+///
+/// A map key becomes an element/tag name and an `@`-field becomes an attribute
+/// value, so only primitive, string-like types make sense; anything that would
+/// serialize to a nested element (a struct, sequence, map, or option) is
+/// rejected with a descriptive error instead of silently producing garbage
+/// markup. When [`KeySerializer::new`] is used the rendered text is also
+/// validated as a legal XML name.
+pub struct KeySerializer {
+    validate_name: bool,
+    /// What the serialized value represents, used to phrase rejection errors
+    /// (`"map key"` vs. `"attribute value"`).
+    role: &'static str,
+}
+
+impl KeySerializer {
+    /// A serializer for map keys: the result must be a legal XML element name.
+    pub fn new() -> Self {
+        KeySerializer {
+            validate_name: true,
+            role: "map key",
+        }
+    }
+
+    /// A serializer for attribute values: any primitive is accepted as-is,
+    /// without XML-name validation.
+    pub fn value() -> Self {
+        KeySerializer {
+            validate_name: false,
+            role: "attribute value",
+        }
+    }
+
+    fn finish(self, value: String) -> Result<String> {
+        if self.validate_name {
+            helpers::validate_xml_name(&value)?;
+        }
+        Ok(value)
+    }
+
+    fn primitive<P: Display>(self, value: P) -> Result<String> {
+        self.finish(value.to_string())
+    }
+
+    fn unsupported(&self, got: &str) -> Error {
+        ErrorKind::UnsupportedOperation(Cow::Owned(format!(
+            "{} must serialize to a primitive, got {}",
+            self.role, got
+        )))
+        .into()
+    }
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<String> {
+        self.primitive(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String> {
+        self.primitive(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String> {
+        self.primitive(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String> {
+        self.primitive(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String> {
+        self.primitive(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String> {
+        self.primitive(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String> {
+        self.primitive(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String> {
+        self.primitive(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String> {
+        self.primitive(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<String> {
+        self.primitive(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<String> {
+        self.primitive(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<String> {
+        self.primitive(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String> {
+        self.finish(v.to_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String> {
+        self.finish(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String> {
+        value.serialize(self)
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<String> {
+        Err(self.unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<String> {
+        Err(self.unsupported("an option"))
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<String> {
+        Err(self.unsupported("an option"))
+    }
+
+    fn serialize_unit(self) -> Result<String> {
+        Err(self.unsupported("a unit"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String> {
+        Err(self.unsupported("a unit struct"))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String> {
+        Err(self.unsupported("an enum variant"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(self.unsupported("a sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(self.unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(self.unsupported("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(self.unsupported("a tuple variant"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(self.unsupported("a map"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct> {
+        Err(self.unsupported("a struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(self.unsupported("a struct variant"))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_key_becomes_tag_name() {
+        let got = "name".serialize(KeySerializer::new()).unwrap();
+        assert_eq!(got, "name");
+    }
+
+    #[test]
+    fn numeric_key_is_not_a_valid_name() {
+        assert!(5u32.serialize(KeySerializer::new()).is_err());
+    }
+
+    #[test]
+    fn key_with_spaces_is_rejected() {
+        assert!("has space".serialize(KeySerializer::new()).is_err());
+    }
+
+    #[test]
+    fn compound_key_is_an_error() {
+        let key = vec![1, 2, 3];
+        assert!(key.serialize(KeySerializer::new()).is_err());
+    }
+
+    #[test]
+    fn attribute_value_skips_name_validation() {
+        let got = "has space".serialize(KeySerializer::value()).unwrap();
+        assert_eq!(got, "has space");
+        let got = 42u32.serialize(KeySerializer::value()).unwrap();
+        assert_eq!(got, "42");
+    }
+
+    #[test]
+    fn compound_attribute_value_is_an_error() {
+        let value = vec![1, 2, 3];
+        assert!(value.serialize(KeySerializer::value()).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::io::Write;
 use std::fmt::Display;
 
@@ -12,6 +13,7 @@ mod var;
 mod seq;
 mod helpers;
 mod tuples;
+mod key;
 
 
 /// A convenience method for serializing some object to a buffer.
@@ -79,12 +81,84 @@ pub fn to_string<S: Serialize>(value: &S) -> Result<String> {
     Ok(string)
 }
 
+/// Serialize a value to a string, naming the outermost element `root` rather
+/// than using the value's type name.
+///
+/// This is the string counterpart to [`Serializer::with_root`] and is the way
+/// to serialize top-level primitives, which otherwise have no element to be
+/// written into.
+pub fn to_string_with_root<S: Serialize>(root: Option<&str>, value: &S) -> Result<String> {
+    let mut writer = Vec::with_capacity(128);
+    {
+        let mut ser = Serializer::with_root(&mut writer, root);
+        value.serialize(&mut ser)?;
+    }
+
+    let string = String::from_utf8(writer)?;
+    Ok(string)
+}
+
+/// Base64 alphabet used to represent byte slices as element text.
+///
+/// XML has no native byte type, so byte slices passed to `serialize_bytes`
+/// (for example `#[serde(with = "serde_bytes")]` fields) are base64-encoded.
+/// A plain `Vec<u8>` or `&[u8]` field is serialized by serde as a sequence of
+/// `u8`, not through `serialize_bytes`, and is rejected as a sequence of
+/// primitives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64Alphabet {
+    /// The standard alphabet (`+`/`/`), matching [`base64::STANDARD`].
+    Standard,
+    /// The URL-safe alphabet (`-`/`_`), matching [`base64::URL_SAFE`].
+    UrlSafe,
+}
+
+impl Base64Alphabet {
+    fn config(self) -> ::base64::Config {
+        match self {
+            Base64Alphabet::Standard => ::base64::STANDARD,
+            Base64Alphabet::UrlSafe => ::base64::URL_SAFE,
+        }
+    }
+}
+
+/// Decode the base64 element text produced by [`serialize_bytes`] back into the
+/// original byte slice.
+///
+/// This is the deserialization counterpart to the byte representation: a
+/// `&[u8]`/`Vec<u8>` field written as base64 element text decodes back to the
+/// exact bytes it was serialized from. Both alphabets are accepted, so the
+/// reader does not need to know which [`Base64Alphabet`] the writer selected.
+///
+/// [`serialize_bytes`]: Serializer::serialize_bytes
+pub fn from_base64(text: &str) -> Result<Vec<u8>> {
+    ::base64::decode_config(text, ::base64::STANDARD)
+        .or_else(|_| ::base64::decode_config(text, ::base64::URL_SAFE))
+        .map_err(|e| ErrorKind::Custom(e.to_string()).into())
+}
+
 /// An XML `Serializer`.
 pub struct Serializer<W>
 where
     W: Write,
 {
     writer: W,
+    /// Element name for the outermost value, overriding the Rust type name.
+    /// Also enables wrapping of top-level primitives and sequence items.
+    root: Option<String>,
+    /// Wrap text containing special characters in `<![CDATA[...]]>` instead of
+    /// entity-escaping it. Off by default.
+    cdata: bool,
+    /// Alphabet used to base64-encode byte slices. Standard by default.
+    base64: Base64Alphabet,
+    /// Indentation unit (fill byte, repeat count) when pretty-printing, or
+    /// `None` for compact output. Compact is the default.
+    indent: Option<(u8, usize)>,
+    /// Current nesting depth, used to size the indent when pretty-printing.
+    depth: usize,
+    /// Set while a sequence/tuple element is being serialized; a bare primitive
+    /// in this context has no element to live in and is rejected.
+    expecting_element: bool,
 }
 
 impl<W> Serializer<W>
@@ -92,20 +166,199 @@ where
     W: Write,
 {
     pub fn new(writer: W) -> Self {
-        Self { writer: writer }
+        Self {
+            writer: writer,
+            root: None,
+            cdata: false,
+            base64: Base64Alphabet::Standard,
+            indent: None,
+            depth: 0,
+            expecting_element: false,
+        }
+    }
+
+    /// Create a `Serializer` that names the outermost element `root` instead of
+    /// relying on the serialized type's name.
+    ///
+    /// With a root name set, top-level primitives and sequences of primitives —
+    /// which otherwise have no element to live in — are wrapped under `root`,
+    /// giving `<root>5</root>` for `5u32` and one `<root>` per sequence item.
+    pub fn with_root(writer: W, root: Option<&str>) -> Self {
+        Self {
+            writer: writer,
+            root: root.map(|r| r.to_string()),
+            cdata: false,
+            base64: Base64Alphabet::Standard,
+            indent: None,
+            depth: 0,
+            expecting_element: false,
+        }
+    }
+
+    /// Select the base64 alphabet used to encode byte slices.
+    pub fn base64_alphabet(mut self, alphabet: Base64Alphabet) -> Self {
+        self.base64 = alphabet;
+        self
+    }
+
+    /// Open the root element if one is configured, returning its name so the
+    /// matching close tag can be emitted by [`close_root`](Serializer::close_root).
+    ///
+    /// The root is consumed on first use, so only the outermost value is
+    /// wrapped; nested values fall back to their own type/field names.
+    fn open_root(&mut self) -> Result<Option<String>> {
+        match self.root.take() {
+            Some(root) => {
+                write!(self.writer, "<{}>", root)?;
+                Ok(Some(root))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn close_root(&mut self, root: Option<String>) -> Result<()> {
+        if let Some(root) = root {
+            write!(self.writer, "</{}>", root)?;
+        }
+        Ok(())
+    }
+
+    /// Emit text content that contains characters significant in XML text
+    /// (`&`, `<`, `>`) as CDATA sections rather than entity-escaping them.
+    /// Text without such characters is written as-is.
+    pub fn cdata(mut self, cdata: bool) -> Self {
+        self.cdata = cdata;
+        self
+    }
+
+    /// Pretty-print the output, inserting a newline before each struct field,
+    /// map entry and tuple struct element (and between sequence items) and
+    /// indenting it by `count` copies of `fill` per nesting level.
+    ///
+    /// Elements whose only content is a single primitive are kept on one line
+    /// (`<age>42</age>`); only elements that contain child elements are broken
+    /// and indented. The outermost element and the inner element of a newtype
+    /// wrapper start on the same line as what precedes them. Compact output
+    /// remains the default.
+    pub fn indent(mut self, fill: u8, count: usize) -> Self {
+        self.indent = Some((fill, count));
+        self
+    }
+
+    /// Whether pretty-printing is enabled.
+    fn pretty(&self) -> bool {
+        self.indent.is_some()
+    }
+
+    /// When pretty-printing, write a newline followed by the indent for the
+    /// current depth. A no-op in compact mode.
+    fn write_indent(&mut self) -> Result<()> {
+        if let Some((fill, count)) = self.indent {
+            self.writer.write_all(b"\n")?;
+            for _ in 0..self.depth * count {
+                self.writer.write_all(&[fill])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Enter a nested element, increasing the indentation depth.
+    fn indent_push(&mut self) {
+        self.depth += 1;
+    }
+
+    /// Leave a nested element, decreasing the indentation depth.
+    fn indent_pop(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Mark that the next value serialized is a sequence/tuple element, so a
+    /// bare primitive (which would have no element to live in) is rejected.
+    fn expect_element(&mut self) {
+        self.expecting_element = true;
+    }
+
+    /// Error if a bare primitive is being serialized as a sequence/tuple
+    /// element. Element-producing methods clear the flag before this fires.
+    fn ensure_element_context(&self) -> Result<()> {
+        if self.expecting_element {
+            Err(ErrorKind::UnsupportedOperation(Cow::Borrowed(
+                "cannot serialize a primitive value as a sequence or tuple element",
+            )).into())
+        } else {
+            Ok(())
+        }
     }
 
     fn write_primitive<P: Display>(&mut self, primitive: P) -> Result<()> {
+        self.ensure_element_context()?;
+        let root = self.open_root()?;
         write!(self.writer, "{}", primitive)?;
-        Ok(())
+        self.close_root(root)
+    }
+
+    /// Write already-formatted primitive text straight to the sink, honouring
+    /// the root wrapping. Used by the numeric fast paths, which format into a
+    /// stack buffer rather than going through `fmt::Display`.
+    fn write_formatted(&mut self, formatted: &str) -> Result<()> {
+        self.ensure_element_context()?;
+        let root = self.open_root()?;
+        self.writer.write_all(formatted.as_bytes())?;
+        self.close_root(root)
     }
 
     fn write_wrapped<S: Serialize>(&mut self, tag: &str, value: S) -> Result<()> {
+        self.expecting_element = false;
         write!(self.writer, "<{}>", tag)?;
         value.serialize(&mut *self)?;
         write!(self.writer, "</{}>", tag)?;
         Ok(())
     }
+
+    /// Write a child element `<tag>value</tag>`, prefixed by the current indent
+    /// when pretty-printing. Used by the `Struct` and `Map` sub-serializers for
+    /// each of their fields/entries.
+    fn write_element<S: Serialize>(&mut self, tag: &str, value: S) -> Result<()> {
+        self.write_indent()?;
+        self.write_wrapped(tag, value)
+    }
+
+    /// Open an element's start tag without emitting its closing `>`.
+    ///
+    /// The `Struct` serializer calls this so that any `@`-prefixed fields can
+    /// be written as attributes on the tag before it is closed with
+    /// [`close_start_tag`](Serializer::close_start_tag).
+    fn open_start_tag(&mut self, tag: &str) -> Result<()> {
+        self.expecting_element = false;
+        write!(self.writer, "<{}", tag)?;
+        Ok(())
+    }
+
+    /// Emit a single attribute (` name="value"`) into an open start tag.
+    ///
+    /// The value is escaped in attribute context, so embedded quotes and
+    /// markup characters round-trip. `value` is the already-rendered text;
+    /// the `Struct` serializer rejects attribute values that do not serialize
+    /// to a primitive before calling this.
+    fn write_attribute(&mut self, name: &str, value: &str) -> Result<()> {
+        write!(self.writer, " {}=\"", name)?;
+        helpers::escape_attribute(&mut self.writer, value)?;
+        write!(self.writer, "\"")?;
+        Ok(())
+    }
+
+    /// Close an open start tag by emitting its `>`.
+    fn close_start_tag(&mut self) -> Result<()> {
+        write!(self.writer, ">")?;
+        Ok(())
+    }
+
+    /// Close an open start tag as an empty, self-closing element (`/>`), used
+    /// for a struct whose fields are all attributes.
+    fn write_self_close(&mut self) -> Result<()> {
+        write!(self.writer, "/>")?;
+        Ok(())
+    }
 }
 
 
@@ -126,69 +379,89 @@ where
     type SerializeStructVariant = Struct<'w, W>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        self.ensure_element_context()?;
+        let root = self.open_root()?;
         if v {
             write!(self.writer, "true")?;
         } else {
             write!(self.writer, "false")?;
         }
-
-        Ok(())
+        self.close_root(root)
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        let mut buf = ::itoa::Buffer::new();
+        self.write_formatted(buf.format(v))
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        let mut buf = ::itoa::Buffer::new();
+        self.write_formatted(buf.format(v))
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        let mut buf = ::itoa::Buffer::new();
+        self.write_formatted(buf.format(v))
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        let mut buf = ::itoa::Buffer::new();
+        self.write_formatted(buf.format(v))
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        let mut buf = ::itoa::Buffer::new();
+        self.write_formatted(buf.format(v))
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        let mut buf = ::itoa::Buffer::new();
+        self.write_formatted(buf.format(v))
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        let mut buf = ::itoa::Buffer::new();
+        self.write_formatted(buf.format(v))
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        let mut buf = ::itoa::Buffer::new();
+        self.write_formatted(buf.format(v))
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        let mut buf = ::ryu::Buffer::new();
+        self.write_formatted(buf.format(v))
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        let mut buf = ::ryu::Buffer::new();
+        self.write_formatted(buf.format(v))
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        let mut buf = [0u8; 4];
+        self.serialize_str(v.encode_utf8(&mut buf))
     }
 
     fn serialize_str(self, value: &str) -> Result<Self::Ok> {
-        self.write_primitive(value)
+        self.ensure_element_context()?;
+        let root = self.open_root()?;
+        if self.cdata && value.contains(|c: char| c == '&' || c == '<' || c == '>') {
+            helpers::write_cdata(&mut self.writer, value)?;
+        } else {
+            helpers::escape_text(&mut self.writer, value)?;
+        }
+        self.close_root(root)
     }
 
     fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok> {
-        // TODO: I imagine you'd want to use base64 here.
-        // Not sure how to roundtrip effectively though...
-        Err(
-            ErrorKind::UnsupportedOperation("serialize_bytes".to_string()).into(),
-        )
+        // XML has no byte type, so represent the slice as base64 element text
+        // (reached by `#[serde(with = "serde_bytes")]` fields; a plain `Vec<u8>`
+        // is serialized by serde as a sequence). `from_base64` is the decoding
+        // counterpart, recovering the original bytes from this element text.
+        let encoded = ::base64::encode_config(value, self.base64.config());
+        self.write_primitive(encoded)
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
@@ -247,6 +520,7 @@ where
         name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
+        self.expecting_element = false;
         write!(self.writer, "<{}>", name)?;
         Ok(Tuple::new_with_name(self, name))
     }
@@ -258,6 +532,7 @@ where
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
+        self.expecting_element = false;
         write!(self.writer, "<{}>", name)?;
         Ok(Tuple::new_with_name(self, name))
     }
@@ -267,8 +542,13 @@ where
     }
 
     fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
-        write!(self.writer, "<{}>", name)?;
-        Ok(Struct::new(self, name))
+        // Leave the start tag open so `@`-prefixed fields can be written as
+        // attributes before the `>` is emitted; `Struct` closes the tag once
+        // the attribute fields have been consumed. A configured root name
+        // overrides the type name for the outermost element.
+        let tag = self.root.take().unwrap_or_else(|| name.to_string());
+        self.open_start_tag(&tag)?;
+        Ok(Struct::new(self, tag))
     }
 
     fn serialize_struct_variant(
@@ -278,8 +558,9 @@ where
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        write!(self.writer, "<{}>", variant)?;
-        Ok(Struct::new(self, variant))
+        let tag = self.root.take().unwrap_or_else(|| variant.to_string());
+        self.open_start_tag(&tag)?;
+        Ok(Struct::new(self, tag))
     }
 }
 
@@ -317,8 +598,10 @@ mod tests {
             let _ = ser.serialize_struct("foo", 0).unwrap();
         }
 
+        // The start tag is left open so attribute fields can be appended; the
+        // closing `>` is emitted by `Struct` once the attributes are consumed.
         let got = String::from_utf8(buffer).unwrap();
-        assert_eq!(got, "<foo>");
+        assert_eq!(got, "<foo");
     }
 
     #[test]
@@ -327,12 +610,14 @@ mod tests {
 
         {
             let mut ser = Serializer::new(&mut buffer);
-            let mut struct_ser = Struct::new(&mut ser, "baz");
+            let mut struct_ser = ser.serialize_struct("baz", 1).unwrap();
             struct_ser.serialize_field("foo", "bar").unwrap();
+            SerializeStruct::end(struct_ser).unwrap();
         }
 
+        // The field flushes the deferred `>` and then becomes a child element.
         let got = String::from_utf8(buffer).unwrap();
-        assert_eq!(got, "<foo>bar</foo>");
+        assert_eq!(got, "<baz><foo>bar</foo></baz>");
     }
 
     #[test]
@@ -359,6 +644,69 @@ mod tests {
         assert_eq!(got, should_be);
     }
 
+    #[test]
+    fn serialize_struct_with_attributes() {
+        #[derive(Serialize)]
+        struct Person {
+            #[serde(rename = "@name")]
+            name: String,
+            #[serde(rename = "@age")]
+            age: u32,
+        }
+
+        let joe = Person {
+            name: "Joe".to_string(),
+            age: 42,
+        };
+        let should_be = "<Person name=\"Joe\" age=\"42\"/>";
+
+        let got = to_string(&joe).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn serialize_struct_mixing_attribute_and_child() {
+        #[derive(Serialize)]
+        struct Person {
+            #[serde(rename = "@name")]
+            name: String,
+            age: u32,
+        }
+
+        let joe = Person {
+            name: "Joe".to_string(),
+            age: 42,
+        };
+        let should_be = "<Person name=\"Joe\"><age>42</age></Person>";
+
+        let got = to_string(&joe).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn pretty_print_struct() {
+        #[derive(Serialize)]
+        struct Person {
+            name: String,
+            age: u32,
+        }
+
+        let joe = Person {
+            name: "Joe".to_string(),
+            age: 42,
+        };
+        let should_be = "<Person>\n  <name>Joe</name>\n  <age>42</age>\n</Person>";
+        let mut buffer = Vec::new();
+
+        {
+            let mut ser = Serializer::new(&mut buffer).indent(b' ', 2);
+            joe.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
     #[test]
     fn test_serialize_map_entries() {
         let should_be = "<name>Bob</name><age>5</age>";
@@ -637,6 +985,21 @@ mod tests {
         assert_eq!(got, should_be);
     }
 
+    #[test]
+    fn top_level_primitive_with_root() {
+        let got = to_string_with_root(Some("root"), &5u32).unwrap();
+        assert_eq!(got, "<root>5</root>");
+
+        let got = to_string_with_root(Some("greeting"), &"hi").unwrap();
+        assert_eq!(got, "<greeting>hi</greeting>");
+    }
+
+    #[test]
+    fn sequence_of_primitives_with_root() {
+        let got = to_string_with_root(Some("root"), &vec![1, 2, 3]).unwrap();
+        assert_eq!(got, "<root>1</root><root>2</root><root>3</root>");
+    }
+
     #[test]
     fn serialize_a_struct_variant() {
         #[derive(Serialize)]
@@ -653,4 +1016,102 @@ mod tests {
         let got = to_string(&f).unwrap();
         assert_eq!(got, should_be);
     }
+
+    #[test]
+    fn special_characters_are_escaped_in_text_and_attributes() {
+        #[derive(Serialize)]
+        struct Doc {
+            #[serde(rename = "@attr")]
+            attr: String,
+            text: String,
+        }
+
+        let doc = Doc {
+            attr: "<a & \"b\">".to_string(),
+            text: "<a & \"b\">".to_string(),
+        };
+        // The attribute context additionally escapes the `"` that would
+        // otherwise terminate the value; text content leaves quotes alone.
+        let should_be = "<Doc attr=\"&lt;a &amp; &quot;b&quot;&gt;\">\
+                         <text>&lt;a &amp; \"b\"&gt;</text></Doc>";
+
+        let got = to_string(&doc).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn cdata_mode_round_trips_literal_terminator() {
+        // Splitting `]]>` across two sections keeps the document well-formed;
+        // a parser concatenating the two CDATA bodies recovers the original
+        // `a]]>b`.
+        let mut buffer = Vec::new();
+        {
+            let mut ser = Serializer::new(&mut buffer).cdata(true);
+            "a]]>b".serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, "<![CDATA[a]]]]><![CDATA[>b]]>");
+    }
+
+    #[test]
+    fn pretty_print_map() {
+        #[derive(Serialize)]
+        struct Foo(BTreeMap<String, u32>);
+
+        let mut map = BTreeMap::new();
+        map.insert("a".to_string(), 5);
+        map.insert("b".to_string(), 6);
+        let foo = Foo(map);
+
+        let should_be = "<Foo>\n  <a>5</a>\n  <b>6</b>\n</Foo>";
+        let mut buffer = Vec::new();
+        {
+            let mut ser = Serializer::new(&mut buffer).indent(b' ', 2);
+            foo.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn pretty_print_nested_struct_with_map() {
+        #[derive(Serialize)]
+        struct Outer {
+            items: BTreeMap<String, u32>,
+        }
+
+        let mut items = BTreeMap::new();
+        items.insert("a".to_string(), 5);
+        items.insert("b".to_string(), 6);
+        let outer = Outer { items };
+
+        let should_be =
+            "<Outer>\n  <items>\n    <a>5</a>\n    <b>6</b>\n  </items>\n</Outer>";
+        let mut buffer = Vec::new();
+        {
+            let mut ser = Serializer::new(&mut buffer).indent(b' ', 2);
+            outer.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn bytes_round_trip_through_base64() {
+        let original: &[u8] = &[0x00, 0xff, 0x10, 0x3d, 0x7e];
+
+        for alphabet in &[Base64Alphabet::Standard, Base64Alphabet::UrlSafe] {
+            let mut buffer = Vec::new();
+            {
+                let mut ser = Serializer::new(&mut buffer).base64_alphabet(*alphabet);
+                ser.serialize_bytes(original).unwrap();
+            }
+            let encoded = String::from_utf8(buffer).unwrap();
+            let decoded = from_base64(&encoded).unwrap();
+            assert_eq!(decoded, original);
+        }
+    }
 }
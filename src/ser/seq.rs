@@ -0,0 +1,66 @@
+use std::io::Write;
+
+use serde::ser::{self, Serialize};
+
+use error::{Error, Result};
+use super::Serializer;
+
+/// Serializes the items of a sequence.
+///
+/// Each item is expected to produce its own element (a struct, newtype, map,
+/// …); a bare primitive has no element to live in and is an error — unless a
+/// root name is configured, in which case every item is wrapped under it
+/// (`<root>1</root><root>2</root>`), which is how lists of primitives are made
+/// serializable.
+pub struct Seq<'w, W>
+where
+    W: 'w + Write,
+{
+    parent: &'w mut Serializer<W>,
+    /// When set, each item is wrapped in `<root>…</root>`.
+    root: Option<String>,
+    /// Whether any item has been written yet, so pretty-printing separates
+    /// siblings with a newline without a leading one.
+    first: bool,
+}
+
+impl<'w, W> Seq<'w, W>
+where
+    W: 'w + Write,
+{
+    pub fn new(parent: &'w mut Serializer<W>) -> Self {
+        parent.expecting_element = false;
+        let root = parent.root.take();
+        Seq {
+            parent: parent,
+            root: root,
+            first: true,
+        }
+    }
+}
+
+impl<'w, W> ser::SerializeSeq for Seq<'w, W>
+where
+    W: 'w + Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        match self.root {
+            Some(ref root) => self.parent.write_element(root, value),
+            None => {
+                if !self.first && self.parent.pretty() {
+                    self.parent.write_indent()?;
+                }
+                self.first = false;
+                self.parent.expect_element();
+                value.serialize(&mut *self.parent)
+            }
+        }
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use std::io::Write;
+
+use serde::ser::{self, Serialize};
+
+use error::{Error, Result};
+use super::Serializer;
+
+/// Serializes the elements of a tuple, tuple struct, or tuple variant.
+///
+/// Like [`Seq`](super::seq::Seq), each element must produce its own element and
+/// a bare primitive is an error; with a root name set, a plain tuple's elements
+/// are wrapped under it. A tuple struct/variant is emitted inside a `<name>…`
+/// element whose closing tag is written by [`end`](ser::SerializeTuple::end).
+pub struct Tuple<'w, W>
+where
+    W: 'w + Write,
+{
+    parent: &'w mut Serializer<W>,
+    /// Enclosing element name for tuple structs/variants, closed on `end`.
+    name: Option<&'static str>,
+    /// When set, each element is wrapped in `<root>…</root>`.
+    root: Option<String>,
+    /// Whether any element has been written yet, for pretty-print separators.
+    first: bool,
+}
+
+impl<'w, W> Tuple<'w, W>
+where
+    W: 'w + Write,
+{
+    pub fn new(parent: &'w mut Serializer<W>) -> Self {
+        parent.expecting_element = false;
+        let root = parent.root.take();
+        Tuple {
+            parent: parent,
+            name: None,
+            root: root,
+            first: true,
+        }
+    }
+
+    pub fn new_with_name(parent: &'w mut Serializer<W>, name: &'static str) -> Self {
+        // The enclosing `<name>` was already written, so drop any root override
+        // rather than letting it leak onto a nested element.
+        parent.root = None;
+        parent.expecting_element = false;
+        Tuple {
+            parent: parent,
+            name: Some(name),
+            root: None,
+            first: true,
+        }
+    }
+
+    fn serialize_element_inner<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        match self.root {
+            Some(ref root) => self.parent.write_element(root, value),
+            None => {
+                if self.name.is_some() {
+                    // Inside a `<name>…</name>` element the children are indented
+                    // one level deeper, like a struct's fields.
+                    if self.first {
+                        self.parent.indent_push();
+                    }
+                    self.parent.write_indent()?;
+                } else if !self.first && self.parent.pretty() {
+                    self.parent.write_indent()?;
+                }
+                self.first = false;
+                self.parent.expect_element();
+                value.serialize(&mut *self.parent)
+            }
+        }
+    }
+
+    fn end_inner(self) -> Result<()> {
+        if let Some(name) = self.name {
+            if !self.first {
+                self.parent.indent_pop();
+                self.parent.write_indent()?;
+            }
+            write!(self.parent.writer, "</{}>", name)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'w, W> ser::SerializeTuple for Tuple<'w, W>
+where
+    W: 'w + Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.serialize_element_inner(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.end_inner()
+    }
+}
+
+impl<'w, W> ser::SerializeTupleStruct for Tuple<'w, W>
+where
+    W: 'w + Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.serialize_element_inner(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.end_inner()
+    }
+}
+
+impl<'w, W> ser::SerializeTupleVariant for Tuple<'w, W>
+where
+    W: 'w + Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.serialize_element_inner(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.end_inner()
+    }
+}
@@ -0,0 +1,197 @@
+use std::borrow::Cow;
+use std::io::Write;
+
+use serde::ser::{self, Serialize};
+
+use error::{Error, ErrorKind, Result};
+use super::Serializer;
+use super::key::KeySerializer;
+
+/// Serializes the entries of a `map` as `<key>value</key>` elements.
+///
+/// Keys are routed through [`KeySerializer`] so only primitive, string-like
+/// keys are accepted and the resulting tag name is validated as a legal XML
+/// name; a struct or sequence key is rejected rather than silently producing
+/// invalid markup.
+pub struct Map<'w, W>
+where
+    W: 'w + Write,
+{
+    parent: &'w mut Serializer<W>,
+    /// The tag name produced by the most recent `serialize_key`, awaiting its
+    /// value.
+    pending: Option<String>,
+    /// Whether any entry has been written yet, used to drive the pretty-print
+    /// indentation around the entries.
+    first: bool,
+}
+
+impl<'w, W> Map<'w, W>
+where
+    W: 'w + Write,
+{
+    pub fn new(parent: &'w mut Serializer<W>) -> Self {
+        parent.expecting_element = false;
+        Map {
+            parent: parent,
+            pending: None,
+            first: true,
+        }
+    }
+}
+
+impl<'w, W> ser::SerializeMap for Map<'w, W>
+where
+    W: 'w + Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.pending = Some(key.serialize(KeySerializer::new())?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let tag = self.pending.take().ok_or_else(|| -> Error {
+            ErrorKind::UnsupportedOperation(Cow::Borrowed(
+                "serialize_value called before serialize_key",
+            )).into()
+        })?;
+        // Enter the entries' depth on the first value so each entry is indented
+        // one level below the enclosing element, mirroring the `Struct` path.
+        if self.first {
+            self.parent.indent_push();
+            self.first = false;
+        }
+        self.parent.write_element(&tag, value)
+    }
+
+    fn end(self) -> Result<()> {
+        // Close the entries' depth and break the line before the enclosing
+        // element's closing tag, so a non-empty map ends with a newline at the
+        // parent's indentation just like a struct does.
+        if !self.first {
+            self.parent.indent_pop();
+            self.parent.write_indent()?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Serializes a struct's fields, emitting `@`-prefixed fields as attributes on
+/// the enclosing start tag and the remaining fields as child elements.
+///
+/// The start tag (`<Tag`) is opened by `serialize_struct` with its closing `>`
+/// deferred; attribute fields are written into it, then the first child field
+/// flushes the `>` and subsequent fields become nested elements. A struct whose
+/// fields are all attributes is emitted as an empty, self-closing element.
+pub struct Struct<'w, W>
+where
+    W: 'w + Write,
+{
+    parent: &'w mut Serializer<W>,
+    name: String,
+    /// Whether the start tag is still open (its `>` not yet written).
+    open: bool,
+}
+
+impl<'w, W> Struct<'w, W>
+where
+    W: 'w + Write,
+{
+    pub fn new<S: Into<String>>(parent: &'w mut Serializer<W>, name: S) -> Self {
+        Struct {
+            parent: parent,
+            name: name.into(),
+            open: true,
+        }
+    }
+
+    /// Emit a child field as a nested element, flushing the start tag's `>` on
+    /// the first child.
+    fn serialize_child<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<()> {
+        if self.open {
+            self.parent.close_start_tag()?;
+            self.parent.indent_push();
+            self.open = false;
+        }
+        self.parent.write_element(key, value)
+    }
+
+    /// Emit an `@`-prefixed field as an attribute on the still-open start tag.
+    fn serialize_attribute<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<()> {
+        if !self.open {
+            return Err(ErrorKind::UnsupportedOperation(Cow::Borrowed(
+                "attribute fields must precede child elements",
+            )).into());
+        }
+        let rendered = value.serialize(KeySerializer::value())?;
+        self.parent.write_attribute(key, &rendered)
+    }
+
+    fn serialize_field_inner<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        if key.starts_with('@') {
+            self.serialize_attribute(&key[1..], value)
+        } else {
+            self.serialize_child(key, value)
+        }
+    }
+
+    fn end_inner(self) -> Result<()> {
+        if self.open {
+            // No child elements were written; emit an empty, self-closing tag.
+            self.parent.write_self_close()
+        } else {
+            self.parent.indent_pop();
+            self.parent.write_indent()?;
+            write!(self.parent.writer, "</{}>", self.name)?;
+            Ok(())
+        }
+    }
+}
+
+impl<'w, W> ser::SerializeStruct for Struct<'w, W>
+where
+    W: 'w + Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.serialize_field_inner(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.end_inner()
+    }
+}
+
+impl<'w, W> ser::SerializeStructVariant for Struct<'w, W>
+where
+    W: 'w + Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.serialize_field_inner(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.end_inner()
+    }
+}